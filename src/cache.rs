@@ -0,0 +1,117 @@
+use eyre::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+#[derive(Serialize, serde::Deserialize)]
+struct OnDisk<T> {
+    value: T,
+    cached_at: Duration,
+}
+
+/// Builds a [`CacheManager`] backed by a single file on disk.
+pub struct CacheManagerBuilder<T> {
+    path: PathBuf,
+    fresh_duration: Option<Duration>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> CacheManagerBuilder<T> {
+    pub fn new(path: PathBuf) -> Self {
+        CacheManagerBuilder {
+            path,
+            fresh_duration: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// How long a value written to the cache stays "fresh" before `get_or_try_init`
+    /// will call its init closure again. `None` means the value never goes stale on
+    /// its own—useful for sidecar caches whose staleness is driven by something else
+    /// (e.g. a paired cache's own freshness window).
+    pub fn with_fresh_duration(mut self, fresh_duration: Option<Duration>) -> Self {
+        self.fresh_duration = fresh_duration;
+        self
+    }
+
+    pub fn build(self) -> CacheManager<T> {
+        CacheManager {
+            path: self.path,
+            fresh_duration: self.fresh_duration,
+            value: OnceLock::new(),
+        }
+    }
+}
+
+/// A disk-backed, process-local in-memory cache for a single value, refreshed via an
+/// init closure once its freshness window has elapsed.
+pub struct CacheManager<T> {
+    path: PathBuf,
+    fresh_duration: Option<Duration>,
+    value: OnceLock<T>,
+}
+
+impl<T> CacheManager<T>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    /// Return the cached value, calling `fetch` to populate/refresh it if missing or
+    /// stale.
+    pub fn get_or_try_init(&self, fetch: impl FnOnce() -> Result<T>) -> Result<&T> {
+        self.get_or_try_init_with_prev(|_prev| fetch())
+    }
+
+    /// Like [`Self::get_or_try_init`], but `fetch` also receives the previously
+    /// cached value (if any), so it can make a conditional request (e.g. with an
+    /// `ETag`) instead of unconditionally refetching from scratch.
+    pub fn get_or_try_init_with_prev(
+        &self,
+        fetch: impl FnOnce(Option<&T>) -> Result<T>,
+    ) -> Result<&T> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let on_disk = self.read_disk();
+        if let Some(fresh) = on_disk.as_ref().filter(|d| self.is_fresh(d)) {
+            return Ok(self.value.get_or_init(|| fresh.value.clone()));
+        }
+        let new_value = fetch(on_disk.as_ref().map(|d| &d.value))?;
+        self.write_disk(&new_value)?;
+        Ok(self.value.get_or_init(|| new_value))
+    }
+
+    fn is_fresh(&self, on_disk: &OnDisk<T>) -> bool {
+        match self.fresh_duration {
+            None => true,
+            Some(fresh_duration) => {
+                let age = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .saturating_sub(on_disk.cached_at);
+                age < fresh_duration
+            }
+        }
+    }
+
+    fn read_disk(&self) -> Option<OnDisk<T>> {
+        let bytes = fs::read(&self.path).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn write_disk(&self, value: &T) -> Result<()> {
+        let on_disk = OnDisk {
+            value: value.clone(),
+            cached_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec(&on_disk)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+pub static MISE_LIST_ALL_VERSIONS: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("MISE_LIST_ALL_VERSIONS").is_ok_and(|v| v == "1" || v == "true"));
+
+/// Path to a PEM file with an extra root certificate to trust, for self-hosted GitLab
+/// instances behind a private/internal CA.
+pub static MISE_GITLAB_CA_CERT: LazyLock<Option<PathBuf>> =
+    LazyLock::new(|| std::env::var_os("MISE_GITLAB_CA_CERT").map(PathBuf::from));
+
+/// Access token sent as a bearer token on GitLab API requests.
+pub static MISE_GITLAB_TOKEN: LazyLock<Option<String>> =
+    LazyLock::new(|| std::env::var("MISE_GITLAB_TOKEN").ok());
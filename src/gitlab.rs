@@ -2,12 +2,16 @@ use crate::cache::{CacheManager, CacheManagerBuilder};
 use crate::{dirs, duration, env};
 use eyre::Result;
 use heck::ToKebabCase;
-use reqwest::header::HeaderMap;
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_NONE_MATCH, RETRY_AFTER};
+use reqwest::{Certificate, StatusCode};
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::LazyLock as Lazy;
-use std::sync::{RwLock, RwLockReadGuard};
+use std::sync::{Condvar, Mutex, RwLock, RwLockReadGuard};
+use std::time::Duration;
 use xx::regex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,15 +34,245 @@ pub struct GitlabAsset {
     pub browser_download_url: String,
 }
 
+/// Raw shape of a release as returned by the GitLab Releases API, see
+/// <https://docs.gitlab.com/ee/api/releases/>. This is converted into
+/// [`GitlabRelease`]/[`GitlabAsset`] so the rest of mise doesn't need to know
+/// about GitLab's asset-link nesting.
+#[derive(Debug, Clone, Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    #[serde(default)]
+    upcoming_release: bool,
+    #[serde(default)]
+    assets: ApiAssets,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ApiAssets {
+    #[serde(default)]
+    links: Vec<ApiAssetLink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiAssetLink {
+    name: String,
+    direct_asset_url: String,
+}
+
+impl From<ApiRelease> for GitlabRelease {
+    fn from(r: ApiRelease) -> Self {
+        GitlabRelease {
+            tag_name: r.tag_name,
+            draft: false,
+            prerelease: r.upcoming_release,
+            assets: r.assets.links.into_iter().map(GitlabAsset::from).collect(),
+        }
+    }
+}
+
+impl From<ApiAssetLink> for GitlabAsset {
+    fn from(l: ApiAssetLink) -> Self {
+        GitlabAsset {
+            name: l.name,
+            browser_download_url: l.direct_asset_url,
+        }
+    }
+}
+
+/// Default GitLab API base for `repo`s that don't specify a self-hosted instance.
+const DEFAULT_API_BASE_URL: &str = "https://gitlab.com";
+
+/// Percent-encode a GitLab project path (e.g. `group/subgroup/project`) the way the
+/// GitLab API expects it when used as the `:id` path parameter, escaping every
+/// non-alphanumeric byte (so `/` becomes `%2F`).
+fn encode_project_id(repo: &str) -> String {
+    repo.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+/// Max attempts (including the first) before giving up on a rate-limited request.
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+/// Upper bound on the computed backoff delay between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A cached value alongside the `ETag` GitLab returned for it, so a stale cache entry
+/// can be revalidated with a conditional request instead of always refetching in full.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Cached<T> {
+    value: T,
+    etag: Option<String>,
+}
+
+/// Raised internally when GitLab responds with a rate-limit error (429, or 403 with a
+/// `Retry-After` header), carrying along the delay GitLab asked for, if any.
+#[derive(Debug)]
+struct RateLimited {
+    retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "gitlab rate limit exceeded")
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Fetch `url` as JSON, sending `etag` as `If-None-Match` when present, and retrying
+/// with exponential backoff if GitLab responds with a rate-limit error. Honors
+/// `Retry-After` when GitLab sends one, otherwise backs off 500ms, 1s, 2s, 4s, ...
+/// with jitter, capped at [`MAX_BACKOFF`].
+///
+/// Returns `Ok(None)` on a `304 Not Modified` (the caller's cached value is still
+/// current), or `Ok(Some((body, headers, etag)))` with the freshly parsed body.
+fn fetch_conditional_with_backoff<T>(
+    url: &str,
+    etag: Option<&str>,
+) -> Result<Option<(T, HeaderMap, Option<String>)>>
+where
+    T: DeserializeOwned,
+{
+    retry_rate_limited(url, || fetch_conditional::<T>(url, etag))
+}
+
+/// Retry `fetch` up to [`MAX_RATE_LIMIT_RETRIES`] total attempts as long as it keeps
+/// failing with [`RateLimited`], sleeping `Retry-After` (or an exponential backoff)
+/// between attempts. Any other error, or the final rate-limited attempt, is returned
+/// as-is. `url` is only used for logging.
+fn retry_rate_limited<T>(url: &str, mut fetch: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        match fetch() {
+            Ok(result) => return Ok(result),
+            Err(err)
+                if attempt + 1 < MAX_RATE_LIMIT_RETRIES
+                    && err.downcast_ref::<RateLimited>().is_some() =>
+            {
+                let retry_after = err.downcast_ref::<RateLimited>().and_then(|r| r.retry_after);
+                let delay = retry_after.unwrap_or_else(|| backoff_delay(attempt));
+                log::debug!(
+                    "gitlab: rate limited fetching {url}, retrying in {delay:?} (attempt {}/{MAX_RATE_LIMIT_RETRIES})",
+                    attempt + 1
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Perform the actual conditional GET against `url` using [`gitlab_client`].
+fn fetch_conditional<T: DeserializeOwned>(
+    url: &str,
+    etag: Option<&str>,
+) -> Result<Option<(T, HeaderMap, Option<String>)>> {
+    let mut req = gitlab_client().get(url);
+    if let Some(etag) = etag {
+        req = req.header(IF_NONE_MATCH, etag);
+    }
+    let resp = req.send()?;
+    let status = resp.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if status.as_u16() == 429 || (status.as_u16() == 403 && resp.headers().contains_key(RETRY_AFTER))
+    {
+        return Err(RateLimited {
+            retry_after: parse_retry_after(resp.headers()),
+        }
+        .into());
+    }
+    let resp = resp.error_for_status()?;
+    let headers = resp.headers().clone();
+    let etag = headers
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body: T = resp.json()?;
+    Ok(Some((body, headers, etag)))
+}
+
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn backoff_delay(attempt: u32) -> Duration {
+    let base = Duration::from_millis(500).saturating_mul(1 << attempt.min(8));
+    let jitter = Duration::from_millis(jitter_ms(250));
+    (base + jitter).min(MAX_BACKOFF)
+}
+
+fn jitter_ms(max: u64) -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    nanos % max.max(1)
+}
+
+/// Lazily-built HTTP client used for all GitLab requests. Configured once with the
+/// self-hosted CA cert (`MISE_GITLAB_CA_CERT`) and auth token, since `HTTP_FETCH`'s
+/// shared client has no way to add a per-provider root certificate.
+fn gitlab_client() -> &'static Client {
+    static CLIENT: Lazy<Client> = Lazy::new(build_gitlab_client);
+    &CLIENT
+}
+
+fn build_gitlab_client() -> Client {
+    let mut builder = Client::builder();
+
+    if let Some(ca_cert_path) = env::MISE_GITLAB_CA_CERT.as_ref() {
+        match std::fs::read(ca_cert_path).map_err(eyre::Report::from).and_then(|pem| {
+            Certificate::from_pem(&pem).map_err(eyre::Report::from)
+        }) {
+            Ok(cert) => builder = builder.add_root_certificate(cert),
+            Err(err) => {
+                log::warn!(
+                    "failed to load MISE_GITLAB_CA_CERT from {}: {err}",
+                    ca_cert_path.display()
+                );
+            }
+        }
+    }
+
+    let mut headers = HeaderMap::new();
+    if let Some(token) = env::MISE_GITLAB_TOKEN.as_ref() {
+        match HeaderValue::from_str(&format!("Bearer {token}")) {
+            Ok(value) => {
+                headers.insert(reqwest::header::AUTHORIZATION, value);
+            }
+            Err(err) => log::warn!("invalid MISE_GITLAB_TOKEN: {err}"),
+        }
+    }
+
+    builder
+        .default_headers(headers)
+        .build()
+        .expect("failed to build gitlab http client")
+}
+
 type CacheGroup<T> = HashMap<String, CacheManager<T>>;
 
-static RELEASES_CACHE: Lazy<RwLock<CacheGroup<Vec<GitlabRelease>>>> = Lazy::new(Default::default);
+static RELEASES_CACHE: Lazy<RwLock<CacheGroup<Cached<Vec<GitlabRelease>>>>> =
+    Lazy::new(Default::default);
 
-static RELEASE_CACHE: Lazy<RwLock<CacheGroup<GitlabRelease>>> = Lazy::new(Default::default);
+static RELEASE_CACHE: Lazy<RwLock<CacheGroup<Cached<GitlabRelease>>>> = Lazy::new(Default::default);
 
-static TAGS_CACHE: Lazy<RwLock<CacheGroup<Vec<String>>>> = Lazy::new(Default::default);
+static TAGS_CACHE: Lazy<RwLock<CacheGroup<Cached<Vec<String>>>>> = Lazy::new(Default::default);
 
-fn get_tags_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Vec<String>>> {
+fn get_tags_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Cached<Vec<String>>>> {
     TAGS_CACHE
         .write()
         .unwrap()
@@ -51,7 +285,7 @@ fn get_tags_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Vec<String>>> {
     TAGS_CACHE.read().unwrap()
 }
 
-fn get_releases_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Vec<GitlabRelease>>> {
+fn get_releases_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Cached<Vec<GitlabRelease>>>> {
     RELEASES_CACHE
         .write()
         .unwrap()
@@ -64,7 +298,7 @@ fn get_releases_cache(key: &str) -> RwLockReadGuard<'_, CacheGroup<Vec<GitlabRel
     RELEASES_CACHE.read().unwrap()
 }
 
-fn get_release_cache<'a>(key: &str) -> RwLockReadGuard<'a, CacheGroup<GitlabRelease>> {
+fn get_release_cache<'a>(key: &str) -> RwLockReadGuard<'a, CacheGroup<Cached<GitlabRelease>>> {
     RELEASE_CACHE
         .write()
         .unwrap()
@@ -78,63 +312,191 @@ fn get_release_cache<'a>(key: &str) -> RwLockReadGuard<'a, CacheGroup<GitlabRele
 }
 
 pub fn list_releases(repo: &str) -> Result<Vec<GitlabRelease>> {
+    list_releases_with_base(repo, DEFAULT_API_BASE_URL)
+}
+
+pub fn list_releases_with_base(repo: &str, api_base_url: &str) -> Result<Vec<GitlabRelease>> {
     let key = repo.to_kebab_case();
     let cache = get_releases_cache(&key);
     let cache = cache.get(&key).unwrap();
-    Ok(cache.get_or_try_init(|| list_releases_(repo))?.to_vec())
+    Ok(cache
+        .get_or_try_init_with_prev(|prev| list_releases_(repo, api_base_url, prev))?
+        .value
+        .clone())
 }
 
-fn list_releases_(repo: &str) -> Result<Vec<GitlabRelease>> {
-    //TODO call api gitlab convert repo to GET /projects/:id/releases
-    // see https://docs.gitlab.com/ee/api/releases/
-    let url = format!("https://api.github.com/repos/{repo}/releases");
-    let (mut releases, mut headers) =
-        crate::http::HTTP_FETCH.json_headers::<Vec<GitlabRelease>, _>(url)?;
+fn list_releases_(
+    repo: &str,
+    api_base_url: &str,
+    prev: Option<&Cached<Vec<GitlabRelease>>>,
+) -> Result<Cached<Vec<GitlabRelease>>> {
+    let project = encode_project_id(repo);
+    let url = format!("{api_base_url}/api/v4/projects/{project}/releases?per_page=100");
+    let etag = prev.and_then(|p| p.etag.as_deref());
+    let (releases, mut headers, mut etag) =
+        match fetch_conditional_with_backoff::<Vec<ApiRelease>>(&url, etag)? {
+            None => return Ok(prev.cloned().unwrap_or(Cached { value: vec![], etag: None })),
+            Some(result) => result,
+        };
+    let mut releases: Vec<GitlabRelease> = releases.into_iter().map(GitlabRelease::from).collect();
 
     if *env::MISE_LIST_ALL_VERSIONS {
-        while let Some(next) = next_page(&headers) {
-            let (more, h) = crate::http::HTTP_FETCH.json_headers::<Vec<GitlabRelease>, _>(next)?;
-            releases.extend(more);
-            headers = h;
+        if let Some((last_url, last)) = last_page(&headers) {
+            if last > 1 {
+                let page_urls: Vec<String> = (2..=last).map(|n| page_url(&last_url, n)).collect();
+                let more: Vec<ApiRelease> = fetch_pages_parallel(&page_urls)?;
+                releases.extend(more.into_iter().map(GitlabRelease::from));
+                // The etag only covers page 1; once merged with later pages it can't be
+                // used to revalidate the whole set on the next refresh.
+                etag = None;
+            }
+        } else {
+            while let Some(next) = next_page(&headers) {
+                let Some((more, h, _)) =
+                    fetch_conditional_with_backoff::<Vec<ApiRelease>>(&next, None)?
+                else {
+                    break;
+                };
+                releases.extend(more.into_iter().map(GitlabRelease::from));
+                headers = h;
+                etag = None;
+            }
         }
     }
     releases.retain(|r| !r.draft && !r.prerelease);
 
-    Ok(releases)
+    Ok(Cached {
+        value: releases,
+        etag,
+    })
 }
 
 pub fn list_tags(repo: &str) -> Result<Vec<String>> {
+    list_tags_with_base(repo, DEFAULT_API_BASE_URL)
+}
+
+pub fn list_tags_with_base(repo: &str, api_base_url: &str) -> Result<Vec<String>> {
     let key = repo.to_kebab_case();
     let cache = get_tags_cache(&key);
     let cache = cache.get(&key).unwrap();
-    Ok(cache.get_or_try_init(|| list_tags_(repo))?.to_vec())
+    Ok(cache
+        .get_or_try_init_with_prev(|prev| list_tags_(repo, api_base_url, prev))?
+        .value
+        .clone())
 }
 
-fn list_tags_(repo: &str) -> Result<Vec<String>> {
-    let url = format!("https://api.github.com/repos/{}/tags", repo);
-    let (mut tags, mut headers) = crate::http::HTTP_FETCH.json_headers::<Vec<GitlabTag>, _>(url)?;
+fn list_tags_(
+    repo: &str,
+    api_base_url: &str,
+    prev: Option<&Cached<Vec<String>>>,
+) -> Result<Cached<Vec<String>>> {
+    let project = encode_project_id(repo);
+    let url = format!("{api_base_url}/api/v4/projects/{project}/repository/tags?per_page=100");
+    let etag = prev.and_then(|p| p.etag.as_deref());
+    let (mut tags, mut headers, mut etag) =
+        match fetch_conditional_with_backoff::<Vec<GitlabTag>>(&url, etag)? {
+            None => return Ok(prev.cloned().unwrap_or(Cached { value: vec![], etag: None })),
+            Some(result) => result,
+        };
 
     if *env::MISE_LIST_ALL_VERSIONS {
-        while let Some(next) = next_page(&headers) {
-            let (more, h) = crate::http::HTTP_FETCH.json_headers::<Vec<GitlabTag>, _>(next)?;
-            tags.extend(more);
-            headers = h;
+        if let Some((last_url, last)) = last_page(&headers) {
+            if last > 1 {
+                let page_urls: Vec<String> = (2..=last).map(|n| page_url(&last_url, n)).collect();
+                let more: Vec<GitlabTag> = fetch_pages_parallel(&page_urls)?;
+                tags.extend(more);
+                etag = None;
+            }
+        } else {
+            while let Some(next) = next_page(&headers) {
+                let Some((more, h, _)) =
+                    fetch_conditional_with_backoff::<Vec<GitlabTag>>(&next, None)?
+                else {
+                    break;
+                };
+                tags.extend(more);
+                headers = h;
+                etag = None;
+            }
         }
     }
 
-    Ok(tags.into_iter().map(|t| t.name).collect())
+    Ok(Cached {
+        value: tags.into_iter().map(|t| t.name).collect(),
+        etag,
+    })
 }
 
 pub fn get_release(repo: &str, tag: &str, api_base_url: &str) -> Result<GitlabRelease> {
     let key = format!("{repo}-{tag}").to_kebab_case();
     let cache = get_release_cache(&key);
     let cache = cache.get(&key).unwrap();
-    Ok(cache.get_or_try_init(|| get_release_(repo, tag, api_base_url))?.clone())
+    Ok(cache
+        .get_or_try_init_with_prev(|prev| get_release_(repo, tag, api_base_url, prev))?
+        .value
+        .clone())
 }
 
-fn get_release_(repo: &str, tag: &str, api_base_url: &str) -> Result<GitlabRelease> {
-    let url = format!("{api_base_url}/projects/{repo}/releases/{tag}");
-    crate::http::HTTP_FETCH.json(url)
+fn get_release_(
+    repo: &str,
+    tag: &str,
+    api_base_url: &str,
+    prev: Option<&Cached<GitlabRelease>>,
+) -> Result<Cached<GitlabRelease>> {
+    let url = format!(
+        "{api_base_url}/api/v4/projects/{}/releases/{}",
+        encode_project_id(repo),
+        encode_project_id(tag)
+    );
+    let etag = prev.and_then(|p| p.etag.as_deref());
+    match fetch_conditional_with_backoff::<ApiRelease>(&url, etag)? {
+        None => prev.cloned().ok_or_else(|| {
+            eyre::eyre!("gitlab returned 304 Not Modified with no cached release for {repo}@{tag}")
+        }),
+        Some((value, _, etag)) => Ok(Cached {
+            value: value.into(),
+            etag,
+        }),
+    }
+}
+
+/// Fetch several releases of `repo` at once, one request per `tag`, bounded at
+/// [`MAX_PARALLEL_REQUESTS`] in flight, instead of paying a full round-trip per tag
+/// sequentially.
+pub fn get_releases(repo: &str, tags: &[&str], api_base_url: &str) -> Result<Vec<GitlabRelease>> {
+    // Register every cache entry up front, so the workers below only take the read lock;
+    // `get_release_cache` takes the write lock, which waits for every read guard held
+    // across another worker's fetch.
+    let keys: Vec<String> = tags.iter().map(|tag| format!("{repo}-{tag}").to_kebab_case()).collect();
+    for key in &keys {
+        drop(get_release_cache(key));
+    }
+    let semaphore = Semaphore::new(MAX_PARALLEL_REQUESTS.min(tags.len().max(1)));
+    let slots: Vec<Mutex<Option<Result<GitlabRelease>>>> =
+        tags.iter().map(|_| Mutex::new(None)).collect();
+
+    std::thread::scope(|scope| {
+        for ((tag, key), slot) in tags.iter().zip(&keys).zip(&slots) {
+            let semaphore = &semaphore;
+            scope.spawn(move || {
+                semaphore.acquire();
+                let result = RELEASE_CACHE.read().unwrap()[key]
+                    .get_or_try_init_with_prev(|prev| get_release_(repo, tag, api_base_url, prev))
+                    .map(|cached| cached.value.clone());
+                semaphore.release();
+                *slot.lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    slots
+        .into_iter()
+        .map(|slot| {
+            slot.into_inner()
+                .unwrap()
+                .expect("every spawned release request stores its result before the thread exits")
+        })
+        .collect()
 }
 
 fn next_page(headers: &HeaderMap) -> Option<String> {
@@ -147,6 +509,239 @@ fn next_page(headers: &HeaderMap) -> Option<String> {
         .map(|c| c.get(1).unwrap().as_str().to_string())
 }
 
+/// Parse the `rel="last"` Link header entry, returning its URL and the `page` query
+/// param it points at, so the remaining pages can be requested directly instead of
+/// walking `rel="next"` one at a time.
+fn last_page(headers: &HeaderMap) -> Option<(String, u32)> {
+    let link = headers
+        .get("link")
+        .map(|l| l.to_str().unwrap_or_default().to_string())
+        .unwrap_or_default();
+    let url = regex!(r#"<([^>]+)>; rel="last""#)
+        .captures(&link)?
+        .get(1)?
+        .as_str()
+        .to_string();
+    let page: u32 = regex!(r"[?&]page=(\d+)")
+        .captures(&url)?
+        .get(1)?
+        .as_str()
+        .parse()
+        .ok()?;
+    Some((url, page))
+}
+
+/// Rewrite the `page` query param of a paginated URL (as found via [`last_page`]) to
+/// point at a different page number.
+fn page_url(template: &str, page: u32) -> String {
+    regex!(r"([?&])page=\d+")
+        .replace(template, format!("${{1}}page={page}").as_str())
+        .into_owned()
+}
+
+/// Cap on in-flight requests when fanning pagination out across threads.
+const MAX_PARALLEL_REQUESTS: usize = 16;
+
+/// A simple counting semaphore used to bound how many pagination requests are in
+/// flight at once.
+struct Semaphore {
+    permits: Mutex<usize>,
+    available: Condvar,
+}
+
+impl Semaphore {
+    fn new(permits: usize) -> Self {
+        Semaphore {
+            permits: Mutex::new(permits),
+            available: Condvar::new(),
+        }
+    }
+
+    fn acquire(&self) {
+        let mut permits = self.permits.lock().unwrap();
+        while *permits == 0 {
+            permits = self.available.wait(permits).unwrap();
+        }
+        *permits -= 1;
+    }
+
+    fn release(&self) {
+        *self.permits.lock().unwrap() += 1;
+        self.available.notify_one();
+    }
+}
+
+/// Fetch a single (already-paginated) page without attempting a conditional request;
+/// pages beyond the first have no cached etag of their own to revalidate against.
+fn fetch_page<T: DeserializeOwned>(url: &str) -> Result<Vec<T>> {
+    let (body, _, _) = require_body(fetch_conditional_with_backoff::<Vec<T>>(url, None)?, url)?;
+    Ok(body)
+}
+
+/// A request sent with no `ETag` should never be answered with `304 Not Modified`,
+/// but some self-hosted/proxied GitLab instances do it anyway. Turn that into an
+/// error instead of unwrapping, since trusting external response data to always
+/// behave is how an unconditional request panics the whole process.
+fn require_body<T>(body: Option<T>, url: &str) -> Result<T> {
+    body.ok_or_else(|| {
+        eyre::eyre!("gitlab returned 304 Not Modified for a request sent with no etag: {url}")
+    })
+}
+
+/// Fetch `urls` concurrently, capped at [`MAX_PARALLEL_REQUESTS`] in flight, and
+/// return their results flattened back together in the same order as `urls`.
+fn fetch_pages_parallel<T>(urls: &[String]) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Send,
+{
+    let semaphore = Semaphore::new(MAX_PARALLEL_REQUESTS.min(urls.len().max(1)));
+    let slots: Vec<Mutex<Option<Result<Vec<T>>>>> = urls.iter().map(|_| Mutex::new(None)).collect();
+
+    std::thread::scope(|scope| {
+        for (url, slot) in urls.iter().zip(&slots) {
+            let semaphore = &semaphore;
+            scope.spawn(move || {
+                semaphore.acquire();
+                let result = fetch_page::<T>(url);
+                semaphore.release();
+                *slot.lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    let mut out = Vec::new();
+    for slot in slots {
+        let result = slot
+            .into_inner()
+            .unwrap()
+            .expect("every spawned page request stores its result before the thread exits");
+        out.extend(result?);
+    }
+    Ok(out)
+}
+
 fn cache_dir() -> PathBuf {
     dirs::CACHE.join("gitlab")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_project_id() {
+        assert_eq!(
+            encode_project_id("group/subgroup/project"),
+            "group%2Fsubgroup%2Fproject"
+        );
+        assert_eq!(encode_project_id("owner/repo"), "owner%2Frepo");
+        assert_eq!(encode_project_id("just-a-repo"), "just%2Da%2Drepo");
+    }
+
+    #[test]
+    fn test_next_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "link",
+            HeaderValue::from_static(
+                r#"<https://gitlab.com/api/v4/projects/1/releases?page=2>; rel="next""#,
+            ),
+        );
+        assert_eq!(
+            next_page(&headers).as_deref(),
+            Some("https://gitlab.com/api/v4/projects/1/releases?page=2")
+        );
+
+        assert_eq!(next_page(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_last_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "link",
+            HeaderValue::from_static(
+                r#"<https://gitlab.com/api/v4/projects/1/releases?page=2>; rel="next", <https://gitlab.com/api/v4/projects/1/releases?page=5>; rel="last""#,
+            ),
+        );
+        let (url, page) = last_page(&headers).unwrap();
+        assert_eq!(url, "https://gitlab.com/api/v4/projects/1/releases?page=5");
+        assert_eq!(page, 5);
+
+        assert_eq!(last_page(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_page_url() {
+        assert_eq!(
+            page_url("https://gitlab.com/api/v4/projects/1/releases?page=3", 7),
+            "https://gitlab.com/api/v4/projects/1/releases?page=7"
+        );
+    }
+
+    #[test]
+    fn test_page_url_does_not_mangle_per_page() {
+        assert_eq!(
+            page_url(
+                "https://gitlab.com/api/v4/projects/1/releases?per_page=100&page=3",
+                7
+            ),
+            "https://gitlab.com/api/v4/projects/1/releases?per_page=100&page=7"
+        );
+    }
+
+    #[test]
+    fn test_backoff_delay_is_bounded() {
+        for attempt in 0..10 {
+            let delay = backoff_delay(attempt);
+            assert!(delay <= MAX_BACKOFF);
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(30)));
+
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_retry_rate_limited_gives_up_after_max_retries() {
+        let mut calls = 0;
+        let result = retry_rate_limited::<()>("https://example.com", || {
+            calls += 1;
+            Err(RateLimited {
+                retry_after: Some(Duration::from_millis(1)),
+            }
+            .into())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, MAX_RATE_LIMIT_RETRIES);
+    }
+
+    #[test]
+    fn test_retry_rate_limited_stops_as_soon_as_fetch_succeeds() {
+        let mut calls = 0;
+        let result = retry_rate_limited("https://example.com", || {
+            calls += 1;
+            if calls < 3 {
+                Err(RateLimited {
+                    retry_after: Some(Duration::from_millis(1)),
+                }
+                .into())
+            } else {
+                Ok(42)
+            }
+        });
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_require_body_errs_instead_of_panicking_on_a_stray_304() {
+        assert!(require_body::<()>(None, "https://example.com").is_err());
+        assert_eq!(require_body(Some(42), "https://example.com").unwrap(), 42);
+    }
+}